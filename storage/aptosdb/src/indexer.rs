@@ -2,26 +2,454 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::AptosDB;
+use crate::schema::index_replication_log::{IndexReplicationLogSchema, ReplicationLogKey};
+use crate::schema::table_index_metadata::{MetadataKey, TableIndexMetadataSchema};
+use crate::schema::table_item_index::{TableItemIndexKey, TableItemIndexSchema, TableItemIndexValue};
 use crate::{TableInfoSchema, OTHER_TIMERS_SECONDS};
 ///! This temporarily implements node internal indexing functionalities on the AptosDB.
 use anyhow::{bail, Result};
+use aptos_logger::warn;
 use aptos_types::access_path::Path;
 use aptos_types::account_address::AccountAddress;
 use aptos_types::state_store::state_key::StateKey;
 use aptos_types::state_store::table::TableHandle;
 use aptos_types::state_store::table::TableInfo;
 use aptos_types::transaction::TransactionToCommit;
+use aptos_types::transaction::Version;
 use aptos_types::write_set::WriteOp;
 use aptos_vm::data_cache::AsMoveResolver;
 use move_deps::move_core_types::identifier::IdentStr;
 use move_deps::move_core_types::language_storage::{StructTag, TypeTag};
 use move_deps::move_core_types::resolver::MoveResolver;
 use move_deps::move_resource_viewer::{AnnotatedMoveValue, MoveValueAnnotator};
-use schemadb::SchemaBatch;
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use schemadb::{ReadOptions, SchemaBatch};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
+use std::io::Write as IoWrite;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 use storage_interface::state_view::DbStateView;
 use storage_interface::DbReader;
 
+/// Whether a table write observed while walking a write set created/updated an
+/// entry or removed it. Mirrors the insert/update vs delete split exposed by
+/// [`WriteOp`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TableChangeOp {
+    /// The op inserted a new entry or overwrote an existing one.
+    Write,
+    /// The op removed an entry.
+    Delete,
+}
+
+/// A single table-item write or deletion in a committed transaction, carrying
+/// enough type information for a subscriber to decide whether it cares without
+/// re-reading the DB. A transaction touching several items of one table yields
+/// one change per item.
+#[derive(Clone, Debug)]
+pub struct TableChange {
+    pub handle: TableHandle,
+    pub key_type: TypeTag,
+    pub value_type: TypeTag,
+    pub op: TableChangeOp,
+}
+
+/// The granular set of table changes produced by a single committed
+/// transaction. Observers are handed one of these per transaction, in commit
+/// order, only after the backing index batch is durably written.
+#[derive(Clone, Debug, Default)]
+pub struct TableChangeSet {
+    pub changes: Vec<TableChange>,
+}
+
+impl TableChangeSet {
+    fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Selects the subset of [`TableChange`]s an observer is interested in. A change
+/// matches if it satisfies the filter; `Any` receives every change.
+#[derive(Clone, Debug)]
+pub enum TableObserverFilter {
+    /// A specific table, identified by its handle.
+    Handle(TableHandle),
+    /// Any table whose key or value type mentions the given struct, either
+    /// directly or inside a type parameter or vector element type.
+    StructTag(StructTag),
+    /// Any table owned by the given account. A table is considered owned by an
+    /// account when either its key or value type mentions a struct declared
+    /// under that address, directly or inside a type parameter or vector.
+    Account(AccountAddress),
+    /// Every change.
+    Any,
+}
+
+impl TableObserverFilter {
+    fn matches(&self, change: &TableChange) -> bool {
+        let mentions_struct = |pred: &dyn Fn(&StructTag) -> bool| {
+            Self::type_mentions(&change.key_type, pred)
+                || Self::type_mentions(&change.value_type, pred)
+        };
+        match self {
+            TableObserverFilter::Handle(handle) => change.handle == *handle,
+            TableObserverFilter::StructTag(tag) => mentions_struct(&|t| t == tag),
+            TableObserverFilter::Account(address) => {
+                mentions_struct(&|t| t.address == *address)
+            }
+            TableObserverFilter::Any => true,
+        }
+    }
+
+    fn type_mentions(type_tag: &TypeTag, pred: &dyn Fn(&StructTag) -> bool) -> bool {
+        match type_tag {
+            TypeTag::Struct(struct_tag) => {
+                pred(struct_tag)
+                    || struct_tag
+                        .type_params
+                        .iter()
+                        .any(|t| Self::type_mentions(t, pred))
+            }
+            TypeTag::Vector(inner) => Self::type_mentions(inner, pred),
+            _ => false,
+        }
+    }
+}
+
+/// Callback invoked with the per-transaction batch of changes that overlap an
+/// observer's filter.
+pub type TableObserverCallback = Arc<dyn Fn(&TableChangeSet) + Send + Sync>;
+
+struct TableObserverRecord {
+    filter: TableObserverFilter,
+    callback: TableObserverCallback,
+}
+
+/// Opaque handle returned by [`TableObserverRegistry::register`] and used to
+/// deregister an observer again.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct TableObserverId(u64);
+
+/// Concurrency-safe registry of table observers, mirroring the transaction
+/// observer pattern. Registration and notification take independent short-lived
+/// locks so a slow callback can never block a new subscriber from registering.
+#[derive(Default)]
+pub struct TableObserverRegistry {
+    next_id: AtomicU64,
+    observers: RwLock<Vec<(TableObserverId, TableObserverRecord)>>,
+}
+
+impl TableObserverRegistry {
+    /// Register `callback` to be invoked with the changes matching `filter`.
+    pub fn register(
+        &self,
+        filter: TableObserverFilter,
+        callback: TableObserverCallback,
+    ) -> TableObserverId {
+        let id = TableObserverId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.observers
+            .write()
+            .unwrap()
+            .push((id, TableObserverRecord { filter, callback }));
+        id
+    }
+
+    /// Remove a previously registered observer. Returns whether an observer was
+    /// actually removed.
+    pub fn deregister(&self, id: TableObserverId) -> bool {
+        let mut observers = self.observers.write().unwrap();
+        let before = observers.len();
+        observers.retain(|(existing, _)| *existing != id);
+        observers.len() != before
+    }
+
+    /// Dispatch one committed transaction's change set to every observer whose
+    /// filter overlaps it, handing each the subset it asked for.
+    fn notify(&self, change_set: &TableChangeSet) {
+        if change_set.is_empty() {
+            return;
+        }
+        // Snapshot the callbacks under the read lock, then invoke them without
+        // holding it so observers may (de)register from within a callback.
+        let matched: Vec<(TableObserverCallback, TableChangeSet)> = {
+            let observers = self.observers.read().unwrap();
+            observers
+                .iter()
+                .filter_map(|(_, record)| {
+                    let changes: Vec<TableChange> = change_set
+                        .changes
+                        .iter()
+                        .filter(|change| record.filter.matches(change))
+                        .cloned()
+                        .collect();
+                    if changes.is_empty() {
+                        None
+                    } else {
+                        Some((record.callback.clone(), TableChangeSet { changes }))
+                    }
+                })
+                .collect()
+        };
+        for (callback, subset) in matched {
+            callback(&subset);
+        }
+    }
+}
+
+/// Process-wide registry. Indexer clients register here to receive a push
+/// stream of table changes instead of polling the index DB.
+pub static TABLE_OBSERVER_REGISTRY: Lazy<TableObserverRegistry> =
+    Lazy::new(TableObserverRegistry::default);
+
+/// Optional external replica that committed index batches are mirrored to. When
+/// unset, indexing stays purely local.
+static INDEX_SINK: Lazy<RwLock<Option<Arc<dyn IndexSink>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Register (or replace) the external datastore that receives committed index
+/// batches. A freshly registered sink can call back into [`AptosDB`] for its
+/// last-seen version to replay everything it missed.
+pub fn register_index_sink(sink: Arc<dyn IndexSink>) {
+    *INDEX_SINK.write().unwrap() = Some(sink);
+}
+
+/// Typed accumulator for the index mutations produced while walking a block's
+/// write sets. Puts are collected here and flushed to the [`SchemaBatch`] in a
+/// single pass at the end, rather than interleaving writes with decoding.
+#[derive(Default)]
+struct IndexBatch {
+    table_info: Vec<(TableHandle, TableInfo)>,
+    table_items: Vec<(TableItemIndexKey, TableItemIndexValue)>,
+    item_deletes: Vec<TableItemIndexKey>,
+    info_deletes: Vec<TableHandle>,
+}
+
+impl IndexBatch {
+    /// Fold a per-thread batch into this one, preserving insertion order.
+    fn merge(&mut self, other: IndexBatch) {
+        self.table_info.extend(other.table_info);
+        self.table_items.extend(other.table_items);
+        self.item_deletes.extend(other.item_deletes);
+        self.info_deletes.extend(other.info_deletes);
+    }
+
+    /// Flush every collected mutation through an [`IndexWriter`] in one pass.
+    fn write_through(self, writer: &mut impl IndexWriter) -> Result<()> {
+        for (handle, info) in self.table_info {
+            writer.put_table_info(handle, &info)?;
+        }
+        for (key, value) in self.table_items {
+            writer.put_table_item(key, value)?;
+        }
+        for key in self.item_deletes {
+            writer.delete_table_item(key)?;
+        }
+        for handle in self.info_deletes {
+            writer.delete_table_info(handle)?;
+        }
+        Ok(())
+    }
+}
+
+/// Backend the index mutations are written through. Decoupling the parsing path
+/// from a concrete [`SchemaBatch`] lets the recursion be exercised against a
+/// lightweight in-memory mock and lets the index be mirrored into an external
+/// datastore.
+pub trait IndexWriter {
+    fn put_table_info(&mut self, handle: TableHandle, info: &TableInfo) -> Result<()>;
+    fn delete_table_info(&mut self, handle: TableHandle) -> Result<()>;
+    fn put_table_item(&mut self, key: TableItemIndexKey, value: TableItemIndexValue) -> Result<()>;
+    fn delete_table_item(&mut self, key: TableItemIndexKey) -> Result<()>;
+    /// Durably commit everything buffered since the last commit and advance the
+    /// index head to `head`, the highest version now reflected.
+    fn commit_batch(&mut self, head: Version) -> Result<()>;
+}
+
+/// [`IndexWriter`] backed by the node's local `index_db`. Buffers into a single
+/// [`SchemaBatch`] and advances the persisted head version atomically with the
+/// data on commit.
+pub struct SchemaDbIndexWriter<'a> {
+    db: &'a schemadb::DB,
+    batch: SchemaBatch,
+    /// Mutations buffered since the last commit, recorded into the durable
+    /// replication log so a replica can replay them from any version later.
+    log: Vec<IndexMutation>,
+}
+
+impl<'a> SchemaDbIndexWriter<'a> {
+    pub fn new(db: &'a schemadb::DB) -> Self {
+        Self {
+            db,
+            batch: SchemaBatch::new(),
+            log: Vec::new(),
+        }
+    }
+}
+
+impl<'a> IndexWriter for SchemaDbIndexWriter<'a> {
+    fn put_table_info(&mut self, handle: TableHandle, info: &TableInfo) -> Result<()> {
+        self.log
+            .push(IndexMutation::PutTableInfo(handle, info.clone()));
+        self.batch.put::<TableInfoSchema>(&handle, info)
+    }
+
+    fn delete_table_info(&mut self, handle: TableHandle) -> Result<()> {
+        self.log.push(IndexMutation::DeleteTableInfo(handle));
+        self.batch.delete::<TableInfoSchema>(&handle)
+    }
+
+    fn put_table_item(&mut self, key: TableItemIndexKey, value: TableItemIndexValue) -> Result<()> {
+        self.log
+            .push(IndexMutation::PutTableItem(key.clone(), value.clone()));
+        self.batch.put::<TableItemIndexSchema>(&key, &value)
+    }
+
+    fn delete_table_item(&mut self, key: TableItemIndexKey) -> Result<()> {
+        self.log.push(IndexMutation::DeleteTableItem(key.clone()));
+        self.batch.delete::<TableItemIndexSchema>(&key)
+    }
+
+    fn commit_batch(&mut self, head: Version) -> Result<()> {
+        let mut batch = std::mem::replace(&mut self.batch, SchemaBatch::new());
+        // Record this batch's mutations in the replication log, keyed by the
+        // head version and their order within the batch, so a lagging replica
+        // can replay from its last-seen version deterministically.
+        for (seq, mutation) in std::mem::take(&mut self.log).into_iter().enumerate() {
+            batch.put::<IndexReplicationLogSchema>(
+                &ReplicationLogKey {
+                    version: head,
+                    seq: seq as u32,
+                },
+                &mutation,
+            )?;
+        }
+        // Persist the head alongside the data so the write is all-or-nothing.
+        batch.put::<TableIndexMetadataSchema>(&MetadataKey::IndexHead, &head)?;
+        self.db.write_schemas(batch)
+    }
+}
+
+/// In-memory [`IndexWriter`], handy for exercising the parsing recursion without
+/// standing up a RocksDB instance.
+#[derive(Default)]
+pub struct InMemoryIndexWriter {
+    pub table_info: HashMap<TableHandle, TableInfo>,
+    pub table_items: HashMap<TableItemIndexKey, TableItemIndexValue>,
+    pub head: Option<Version>,
+}
+
+impl IndexWriter for InMemoryIndexWriter {
+    fn put_table_info(&mut self, handle: TableHandle, info: &TableInfo) -> Result<()> {
+        self.table_info.insert(handle, info.clone());
+        Ok(())
+    }
+
+    fn delete_table_info(&mut self, handle: TableHandle) -> Result<()> {
+        self.table_info.remove(&handle);
+        Ok(())
+    }
+
+    fn put_table_item(&mut self, key: TableItemIndexKey, value: TableItemIndexValue) -> Result<()> {
+        self.table_items.insert(key, value);
+        Ok(())
+    }
+
+    fn delete_table_item(&mut self, key: TableItemIndexKey) -> Result<()> {
+        self.table_items.remove(&key);
+        Ok(())
+    }
+
+    fn commit_batch(&mut self, head: Version) -> Result<()> {
+        self.head = Some(head);
+        Ok(())
+    }
+}
+
+/// A single mutation shipped to an external replica, in commit order. Also the
+/// value type of the durable replication log, hence the serde derives.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum IndexMutation {
+    PutTableInfo(TableHandle, TableInfo),
+    DeleteTableInfo(TableHandle),
+    PutTableItem(TableItemIndexKey, TableItemIndexValue),
+    DeleteTableItem(TableItemIndexKey),
+}
+
+/// Sink an external datastore implements to receive committed index batches. The
+/// `head` is the version the batch advances the index to; a consumer persists it
+/// and, after a restart, can ask to be re-shipped everything past its last-seen
+/// value rather than re-indexing from genesis.
+pub trait IndexSink: Send + Sync {
+    fn apply_batch(&self, head: Version, mutations: &[IndexMutation]) -> Result<()>;
+}
+
+/// [`IndexWriter`] adapter that writes through to an inner (local) writer and,
+/// on each successful commit, ships the same batch to an external sink. Local
+/// durability still gates replication: the sink only sees a batch after the
+/// inner writer commits it.
+pub struct ReplicatingIndexWriter<'a, W: IndexWriter> {
+    inner: W,
+    sink: &'a dyn IndexSink,
+    pending: Vec<IndexMutation>,
+}
+
+impl<'a, W: IndexWriter> ReplicatingIndexWriter<'a, W> {
+    pub fn new(inner: W, sink: &'a dyn IndexSink) -> Self {
+        Self {
+            inner,
+            sink,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<'a, W: IndexWriter> IndexWriter for ReplicatingIndexWriter<'a, W> {
+    fn put_table_info(&mut self, handle: TableHandle, info: &TableInfo) -> Result<()> {
+        self.pending
+            .push(IndexMutation::PutTableInfo(handle, info.clone()));
+        self.inner.put_table_info(handle, info)
+    }
+
+    fn delete_table_info(&mut self, handle: TableHandle) -> Result<()> {
+        self.pending.push(IndexMutation::DeleteTableInfo(handle));
+        self.inner.delete_table_info(handle)
+    }
+
+    fn put_table_item(&mut self, key: TableItemIndexKey, value: TableItemIndexValue) -> Result<()> {
+        self.pending
+            .push(IndexMutation::PutTableItem(key.clone(), value.clone()));
+        self.inner.put_table_item(key, value)
+    }
+
+    fn delete_table_item(&mut self, key: TableItemIndexKey) -> Result<()> {
+        self.pending
+            .push(IndexMutation::DeleteTableItem(key.clone()));
+        self.inner.delete_table_item(key)
+    }
+
+    fn commit_batch(&mut self, head: Version) -> Result<()> {
+        // Commit locally first so the replica never observes a batch that is not
+        // yet durable on this node. The inner writer also appends these
+        // mutations to the durable replication log.
+        self.inner.commit_batch(head)?;
+        let mutations = std::mem::take(&mut self.pending);
+        // Shipping is best-effort: the batch is durable locally and in the
+        // replication log, so a transient sink failure is recoverable. The
+        // replica catches up later via `replay_index_mutations_since` from its
+        // last-seen version; a flaky external sink must not abort local indexing.
+        if let Err(err) = self.sink.apply_batch(head, &mutations) {
+            warn!(
+                "Failed to ship index batch at head {} to sink: {}. Replica can replay it.",
+                head, err
+            );
+        }
+        Ok(())
+    }
+}
+
 impl AptosDB {
     pub fn index_transactions(&self, txns_to_commit: &[TransactionToCommit]) -> Result<()> {
         if txns_to_commit.is_empty() {
@@ -42,58 +470,345 @@ impl AptosDB {
         let resolver = state_view.as_move_resolver();
         let annotator = MoveValueAnnotator::new(&resolver);
 
-        let mut batch = SchemaBatch::new();
+        let first_version = state_view.version.unwrap_or(0);
+
+        let mut puts = IndexBatch::default();
+
+        // First pass: discover every table handle touched by a resource write in
+        // this block and prune the handles owned by deleted resources. Seeding
+        // the handle cache here guarantees that a table created and used within
+        // the same block is never looked up in `index_db`.
+        let mut handles: HashMap<TableHandle, TableInfo> = HashMap::new();
         for txn_to_commit in txns_to_commit {
             for (state_key, write_op) in txn_to_commit.write_set() {
-                self.parse_table_info_from_write_op(&annotator, state_key, write_op, &mut batch)?;
+                match (write_op, state_key) {
+                    (WriteOp::Value(bytes), StateKey::AccessPath(access_path)) => {
+                        if let Path::Resource(struct_tag) = (&access_path.path).try_into()? {
+                            Self::parse_table_info(
+                                &annotator.view_value(&TypeTag::Struct(struct_tag), bytes)?,
+                                &mut puts,
+                                &mut handles,
+                            )?;
+                        }
+                    }
+                    (WriteOp::Deletion, StateKey::AccessPath(access_path)) => {
+                        if let Ok(Path::Resource(struct_tag)) = (&access_path.path).try_into() {
+                            self.prune_deleted_resource_tables(
+                                &annotator,
+                                &resolver,
+                                access_path.address,
+                                struct_tag,
+                                &mut puts,
+                            );
+                        }
+                    }
+                    _ => {}
+                }
             }
         }
-        self.index_db.write_schemas(batch)
+
+        // Deleting a resource orphans the table handles it owned (pushed into
+        // `info_deletes` above). Their item-index entries must go too, otherwise
+        // the self-check sees items pointing at a pruned handle. `TableItemIndexKey`
+        // is handle-prefixed big-endian, so seek to each orphaned handle's prefix
+        // and scan only that contiguous range rather than the whole column family.
+        if !puts.info_deletes.is_empty() {
+            let orphaned: HashSet<TableHandle> = puts.info_deletes.iter().copied().collect();
+            for handle in orphaned {
+                let mut item_iter = self
+                    .index_db
+                    .iter::<TableItemIndexSchema>(ReadOptions::default())?;
+                item_iter.seek(&TableItemIndexKey {
+                    handle,
+                    encoded_key: Vec::new(),
+                })?;
+                for entry in item_iter {
+                    let (key, _) = entry?;
+                    // Ordered scan: the first key of a different handle ends this
+                    // handle's range.
+                    if key.handle != handle {
+                        break;
+                    }
+                    puts.item_deletes.push(key);
+                }
+            }
+        }
+
+        // Resolve any pre-existing handles referenced by item writes against the
+        // DB exactly once, so the decode pass below touches only the in-memory
+        // cache.
+        let to_resolve: HashSet<TableHandle> = txns_to_commit
+            .iter()
+            .flat_map(|txn| txn.write_set())
+            .filter_map(|(state_key, _)| match state_key {
+                StateKey::TableItem { handle, .. } if !handles.contains_key(handle) => Some(*handle),
+                _ => None,
+            })
+            .collect();
+        for handle in to_resolve {
+            if let Ok(table_info) = self.get_table_info(handle) {
+                handles.insert(handle, table_info);
+            }
+        }
+
+        // Second pass: decode the item writes across transactions on the rayon
+        // pool. Handles are fully resolved and the map is read-only. Each worker
+        // builds its own resolver + `MoveValueAnnotator` over the shared,
+        // read-only `DbStateView`: the annotator keeps a non-synchronized
+        // struct-layout cache, so it must never be shared across threads, but
+        // the state view itself is `Sync` and serves concurrent reads. Each
+        // transaction produces its own batch and change set, merged back in
+        // commit order.
+        let handles = &handles;
+        let state_view = &state_view;
+        let per_txn: Vec<(IndexBatch, TableChangeSet)> = txns_to_commit
+            .par_iter()
+            .enumerate()
+            .map(|(txn_offset, txn_to_commit)| {
+                let resolver = state_view.as_move_resolver();
+                let annotator = MoveValueAnnotator::new(&resolver);
+                let version = first_version + txn_offset as Version;
+                let mut local = IndexBatch::default();
+                let mut change_set = TableChangeSet::default();
+                for (state_key, write_op) in txn_to_commit.write_set() {
+                    self.index_item_write_op(
+                        &annotator,
+                        state_key,
+                        write_op,
+                        version,
+                        handles,
+                        &mut local,
+                        &mut change_set,
+                    );
+                }
+                (local, change_set)
+            })
+            .collect();
+
+        let mut change_sets = Vec::with_capacity(per_txn.len());
+        for (local, change_set) in per_txn {
+            puts.merge(local);
+            change_sets.push(change_set);
+        }
+
+        // Flush every collected mutation through the index writer in a single
+        // pass and commit, advancing the head to the last version indexed. When
+        // an external replica has been registered, mirror the batch to it.
+        let head = first_version + txns_to_commit.len() as Version - 1;
+        let mut writer = SchemaDbIndexWriter::new(&self.index_db);
+        match INDEX_SINK.read().unwrap().as_ref() {
+            Some(sink) => {
+                let mut writer = ReplicatingIndexWriter::new(writer, sink.as_ref());
+                puts.write_through(&mut writer)?;
+                writer.commit_batch(head)?;
+            }
+            None => {
+                puts.write_through(&mut writer)?;
+                writer.commit_batch(head)?;
+            }
+        }
+
+        // The batch is now durable; notify observers in commit order.
+        for change_set in &change_sets {
+            TABLE_OBSERVER_REGISTRY.notify(change_set);
+        }
+        Ok(())
     }
 
-    fn parse_table_info_from_write_op(
+    /// Decode a single item-level write op against the pre-resolved handle
+    /// cache. Purely in-memory: it never reads `index_db`, so it is safe to run
+    /// on a worker thread.
+    #[allow(clippy::too_many_arguments)]
+    fn index_item_write_op(
         &self,
         annotator: &MoveValueAnnotator<impl MoveResolver>,
         state_key: &StateKey,
         write_op: &WriteOp,
-        batch: &mut SchemaBatch,
-    ) -> Result<()> {
+        version: Version,
+        handles: &HashMap<TableHandle, TableInfo>,
+        puts: &mut IndexBatch,
+        change_set: &mut TableChangeSet,
+    ) {
+        let (handle, key) = match state_key {
+            StateKey::TableItem { handle, key } => (*handle, key),
+            _ => return,
+        };
+        let table_info = match handles.get(&handle) {
+            Some(table_info) => table_info,
+            None => return,
+        };
         match write_op {
-            WriteOp::Value(bytes) => match state_key {
-                StateKey::AccessPath(access_path) => {
-                    let path: Path = (&access_path.path).try_into()?;
-                    match path {
-                        Path::Code(_) => (),
-                        Path::Resource(struct_tag) => self.parse_table_info(
-                            &annotator.view_value(&TypeTag::Struct(struct_tag), bytes)?,
-                            batch,
-                        )?,
-                    }
+            WriteOp::Value(bytes) => {
+                // Decode the value once: render it to a stable string for the
+                // index and reuse the decoded value to discover nested tables.
+                let decoded = annotator.view_value(&table_info.value_type, bytes).ok();
+                let rendered = decoded.as_ref().map(|v| v.to_string()).unwrap_or_default();
+                self.index_table_item(
+                    annotator, handle, key, table_info, version, rendered, puts, change_set,
+                );
+                // Nested tables in the item value are discovered into this
+                // thread-local scratch map; only their info rows matter here.
+                if let Some(value) = decoded {
+                    let mut scratch = HashMap::new();
+                    let _ = Self::parse_table_info(&value, puts, &mut scratch);
                 }
-                StateKey::TableItem { handle, .. } => {
-                    let table_info = self.get_table_info(*handle)?;
-                    self.parse_table_info(
-                        &annotator.view_value(&table_info.value_type, bytes)?,
-                        batch,
-                    )?
+            }
+            WriteOp::Deletion => {
+                change_set.changes.push(TableChange {
+                    handle,
+                    key_type: table_info.key_type.clone(),
+                    value_type: table_info.value_type.clone(),
+                    op: TableChangeOp::Delete,
+                });
+                self.delete_table_item(annotator, handle, key, table_info, puts);
+            }
+            WriteOp::Delta(_, _) => {
+                // A delta is a numeric aggregator change: there are no nested
+                // tables to discover in it, and the authoritative post-commit
+                // value is not in `state_view` (pinned at the pre-block
+                // version), so materializing it here is not possible. We only
+                // record that the item was touched at this version with an empty
+                // rendered value; the numeric value itself is not indexed.
+                self.index_table_item(
+                    annotator,
+                    handle,
+                    key,
+                    table_info,
+                    version,
+                    String::new(),
+                    puts,
+                    change_set,
+                );
+            }
+        }
+    }
+
+    /// Remove a table item's entry from the item index on deletion. Decoding
+    /// the key is best-effort: a failure is logged and skipped.
+    fn delete_table_item(
+        &self,
+        annotator: &MoveValueAnnotator<impl MoveResolver>,
+        handle: TableHandle,
+        key: &[u8],
+        table_info: &TableInfo,
+        puts: &mut IndexBatch,
+    ) {
+        match annotator
+            .view_value(&table_info.key_type, key)
+            .and_then(|decoded| Ok(bcs::to_bytes(&decoded.to_string())?))
+        {
+            Ok(encoded_key) => puts.item_deletes.push(TableItemIndexKey {
+                handle,
+                encoded_key,
+            }),
+            Err(err) => warn!(
+                "Failed to decode deleted table item key for handle {:?}: {}",
+                handle, err
+            ),
+        }
+    }
+
+    /// On deletion of a resource, read its pre-deletion value, find any table
+    /// handles it owned and prune their [`TableInfoSchema`] rows so the index
+    /// reflects exactly the live set of handles.
+    fn prune_deleted_resource_tables(
+        &self,
+        annotator: &MoveValueAnnotator<impl MoveResolver>,
+        resolver: &impl MoveResolver,
+        address: AccountAddress,
+        struct_tag: StructTag,
+        puts: &mut IndexBatch,
+    ) {
+        let bytes = match resolver.get_resource(&address, &struct_tag) {
+            Ok(Some(bytes)) => bytes,
+            // Nothing to prune if the prior value is gone or unreadable.
+            _ => return,
+        };
+        let value = match annotator.view_value(&TypeTag::Struct(struct_tag), &bytes) {
+            Ok(value) => value,
+            Err(err) => {
+                warn!("Failed to decode deleted resource for table pruning: {}", err);
+                return;
+            }
+        };
+        Self::collect_table_handles(&value, &mut puts.info_deletes);
+    }
+
+    /// Recursively collect every table handle reachable from an annotated value.
+    fn collect_table_handles(move_value: &AnnotatedMoveValue, out: &mut Vec<TableHandle>) {
+        match move_value {
+            AnnotatedMoveValue::Vector(_, items) => {
+                for item in items {
+                    Self::collect_table_handles(item, out);
                 }
-                StateKey::Raw(_) => (),
-            },
-            WriteOp::Deletion => (),
-            WriteOp::Delta(_, _) => (),
+            }
+            AnnotatedMoveValue::Struct(struct_value) => {
+                if Self::is_table(&struct_value.type_) {
+                    if let Some((_, AnnotatedMoveValue::U128(handle))) = struct_value.value.first() {
+                        out.push(TableHandle(*handle));
+                    }
+                } else {
+                    for (_, field) in &struct_value.value {
+                        Self::collect_table_handles(field, out);
+                    }
+                }
+            }
+            _ => {}
         }
-        Ok(())
     }
 
-    fn parse_table_info(
+    /// Decode a table item key against its `key_type` and record the
+    /// `(handle, encoded_key) -> (version, rendered value)` mapping. `rendered`
+    /// is the value rendered to a stable string, or empty when it could not be
+    /// materialized. Decoding failures are logged and skipped rather than
+    /// propagated.
+    #[allow(clippy::too_many_arguments)]
+    fn index_table_item(
         &self,
+        annotator: &MoveValueAnnotator<impl MoveResolver>,
+        handle: TableHandle,
+        key: &[u8],
+        table_info: &TableInfo,
+        version: Version,
+        rendered: String,
+        puts: &mut IndexBatch,
+        change_set: &mut TableChangeSet,
+    ) {
+        change_set.changes.push(TableChange {
+            handle,
+            key_type: table_info.key_type.clone(),
+            value_type: table_info.value_type.clone(),
+            op: TableChangeOp::Write,
+        });
+        match annotator
+            .view_value(&table_info.key_type, key)
+            .and_then(|decoded| Ok(bcs::to_bytes(&decoded.to_string())?))
+        {
+            Ok(encoded_key) => puts.table_items.push((
+                TableItemIndexKey {
+                    handle,
+                    encoded_key,
+                },
+                TableItemIndexValue { version, rendered },
+            )),
+            Err(err) => warn!(
+                "Failed to decode table item key for handle {:?}: {}",
+                handle, err
+            ),
+        }
+    }
+
+    // Does not touch `self`; an associated function so tests can drive the
+    // recursion through an [`InMemoryIndexWriter`] without a RocksDB instance.
+    fn parse_table_info(
         move_value: &AnnotatedMoveValue,
-        batch: &mut SchemaBatch,
+        puts: &mut IndexBatch,
+        discovered: &mut HashMap<TableHandle, TableInfo>,
     ) -> Result<()> {
         match move_value {
             AnnotatedMoveValue::Vector(_type_tag, items) => {
                 for item in items {
-                    self.parse_table_info(item, batch)?;
+                    Self::parse_table_info(item, puts, discovered)?;
                 }
             }
             AnnotatedMoveValue::Struct(struct_value) => {
@@ -107,15 +822,18 @@ impl AptosDB {
                     let table_handle = match &struct_value.value[0] {
                         (name, AnnotatedMoveValue::U128(handle)) => {
                             assert_eq!(name.as_ref(), IdentStr::new("handle").unwrap());
-                            println!("found table. {} {:?}", handle, table_info);
                             TableHandle(*handle)
                         }
                         _ => bail!("Table struct malformed. {:?}", struct_value),
                     };
-                    batch.put::<TableInfoSchema>(&table_handle, &table_info)?;
+                    // Remember the handle for the rest of the batch so items
+                    // written against a table created in this very commit can be
+                    // decoded without a DB round-trip.
+                    discovered.insert(table_handle, table_info.clone());
+                    puts.table_info.push((table_handle, table_info));
                 } else {
                     for (_identifier, field) in &struct_value.value {
-                        self.parse_table_info(&field, batch)?;
+                        Self::parse_table_info(&field, puts, discovered)?;
                     }
                 }
             }
@@ -131,9 +849,431 @@ impl AptosDB {
         Ok(())
     }
 
+    /// The highest version durably reflected in the index, or `None` if nothing
+    /// has been indexed yet. A lagging external replica uses this as the upper
+    /// bound when catching up from its own last-seen version.
+    pub fn index_head_version(&self) -> Result<Option<Version>> {
+        self.index_db.get::<TableIndexMetadataSchema>(&MetadataKey::IndexHead)
+    }
+
+    /// Re-ship every index mutation committed after `since` to `sink`, grouped
+    /// by the head version of the batch that produced them and in commit order.
+    ///
+    /// This is the catch-up path: a lagging or restarted replica reports its
+    /// last-seen version and receives exactly the mutations it missed from the
+    /// durable replication log, so it converges deterministically without
+    /// re-indexing from genesis. Because the primary index keeps only the
+    /// latest version per key, the replay log — not the index — is the source
+    /// of truth for replay.
+    pub fn replay_index_mutations_since(
+        &self,
+        since: Version,
+        sink: &dyn IndexSink,
+    ) -> Result<()> {
+        let mut iter = self
+            .index_db
+            .iter::<IndexReplicationLogSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        let mut current: Option<(Version, Vec<IndexMutation>)> = None;
+        for entry in iter {
+            let (key, mutation) = entry?;
+            if key.version <= since {
+                continue;
+            }
+            match current.as_mut() {
+                Some((version, batch)) if *version == key.version => batch.push(mutation),
+                _ => {
+                    if let Some((version, batch)) = current.take() {
+                        sink.apply_batch(version, &batch)?;
+                    }
+                    current = Some((key.version, vec![mutation]));
+                }
+            }
+        }
+        if let Some((version, batch)) = current {
+            sink.apply_batch(version, &batch)?;
+        }
+        Ok(())
+    }
+
+    /// Trim the replication log, dropping every mutation at a head version below
+    /// `below`. The log is an append-only mirror of index history and would
+    /// otherwise grow without bound; operators call this with the minimum
+    /// last-seen version across all replicas, so nothing a replica might still
+    /// need to replay is removed. Log keys are version-then-seq big-endian, so
+    /// the ordered scan stops at the first entry that must be kept.
+    pub fn prune_index_replication_log(&self, below: Version) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        let mut iter = self
+            .index_db
+            .iter::<IndexReplicationLogSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        for entry in iter {
+            let (key, _) = entry?;
+            if key.version >= below {
+                break;
+            }
+            batch.delete::<IndexReplicationLogSchema>(&key)?;
+        }
+        self.index_db.write_schemas(batch)
+    }
+
+    /// Self-check mode: scan the index and assert it has no dangling items.
+    /// Every item in [`TableItemIndexSchema`] must point at a handle still
+    /// present in [`TableInfoSchema`]; a dangling item means a handle was pruned
+    /// without its items being removed. Only the index itself is scanned: its
+    /// handles and items are not compared against the ledger's live state.
+    pub fn check_index_consistency(&self) -> Result<()> {
+        let mut handles = HashSet::new();
+        let mut info_iter = self
+            .index_db
+            .iter::<TableInfoSchema>(ReadOptions::default())?;
+        info_iter.seek_to_first();
+        for entry in info_iter {
+            let (handle, _) = entry?;
+            handles.insert(handle);
+        }
+
+        let mut item_iter = self
+            .index_db
+            .iter::<TableItemIndexSchema>(ReadOptions::default())?;
+        item_iter.seek_to_first();
+        for entry in item_iter {
+            let (key, _) = entry?;
+            if !handles.contains(&key.handle) {
+                bail!(
+                    "Index inconsistency: table item references dangling handle {:?}",
+                    key.handle
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Stream the contents of the table-info index (and, when present, the
+    /// decoded table-item index) to CSV writers for offline analysis.
+    ///
+    /// `handle_filter`, when set, restricts the dump to a single table;
+    /// `version_range`, when set, keeps only item rows last written within the
+    /// inclusive version window. Rows are written incrementally as the
+    /// column-family iterators advance, so exporting a multi-gigabyte table does
+    /// not buffer it in memory.
+    ///
+    /// The `table_info` writer receives `handle,key_type,value_type` rows (the
+    /// handle as a hex `u128`, the types as type-tag strings); the `table_items`
+    /// writer receives `handle,key,value,version` rows, where `key` and `value`
+    /// are rendered to the same stable string form used by the index. `value` is
+    /// empty for entries whose value could not be materialized (e.g. aggregator
+    /// deltas), which is all the item index retains for them.
+    ///
+    /// Records are emitted with minimal RFC 4180 quoting by hand rather than
+    /// pulling in a CSV crate, keeping the export dependency-free.
+    pub fn dump_index_to_csv<I: IoWrite, T: IoWrite>(
+        &self,
+        mut table_info: I,
+        mut table_items: T,
+        handle_filter: Option<TableHandle>,
+        version_range: Option<RangeInclusive<Version>>,
+    ) -> Result<()> {
+        write_csv_record(&mut table_info, &["handle", "key_type", "value_type"])?;
+        let mut info_iter = self
+            .index_db
+            .iter::<TableInfoSchema>(ReadOptions::default())?;
+        info_iter.seek_to_first();
+        for entry in info_iter {
+            let (handle, info) = entry?;
+            if handle_filter.map_or(false, |filter| filter != handle) {
+                continue;
+            }
+            write_csv_record(
+                &mut table_info,
+                &[
+                    &format!("{:#x}", handle.0),
+                    &info.key_type.to_string(),
+                    &info.value_type.to_string(),
+                ],
+            )?;
+        }
+        table_info.flush()?;
+
+        write_csv_record(&mut table_items, &["handle", "key", "value", "version"])?;
+        let mut item_iter = self
+            .index_db
+            .iter::<TableItemIndexSchema>(ReadOptions::default())?;
+        item_iter.seek_to_first();
+        for entry in item_iter {
+            let (key, value) = entry?;
+            if handle_filter.map_or(false, |filter| filter != key.handle) {
+                continue;
+            }
+            if version_range
+                .as_ref()
+                .map_or(false, |range| !range.contains(&value.version))
+            {
+                continue;
+            }
+            // The encoded key is the BCS of its stable string rendering; decode
+            // it back so the CSV holds the human-readable key.
+            let rendered_key = bcs::from_bytes::<String>(&key.encoded_key)
+                .unwrap_or_else(|_| hex_encode(&key.encoded_key));
+            write_csv_record(
+                &mut table_items,
+                &[
+                    &format!("{:#x}", key.handle.0),
+                    &rendered_key,
+                    &value.rendered,
+                    &value.version.to_string(),
+                ],
+            )?;
+        }
+        table_items.flush()?;
+        Ok(())
+    }
+
     fn is_table(struct_tag: &StructTag) -> bool {
         struct_tag.address == AccountAddress::ONE
             && struct_tag.module.as_ref() == IdentStr::new("table").unwrap()
             && struct_tag.name.as_ref() == IdentStr::new("Table").unwrap()
     }
 }
+
+/// Write one CSV record, quoting per RFC 4180 only where needed (a field is
+/// quoted if it contains a comma, quote, or newline; embedded quotes are
+/// doubled). Kept local so the export pulls in no CSV dependency.
+fn write_csv_record<W: IoWrite>(writer: &mut W, fields: &[&str]) -> Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        if field.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+            writer.write_all(b"\"")?;
+            writer.write_all(field.replace('"', "\"\"").as_bytes())?;
+            writer.write_all(b"\"")?;
+        } else {
+            writer.write_all(field.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Lowercase hex encoding, used only as a fallback when an item's encoded key is
+/// not the expected BCS string. Avoids a `hex` dependency for this one call.
+fn hex_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        out.push_str(&format!("{:02x}", byte));
+    }
+    out
+}
+
+#[cfg(test)]
+mod observer_tests {
+    use super::*;
+    use move_deps::move_core_types::identifier::Identifier;
+
+    fn struct_tag(address: AccountAddress, module: &str, name: &str) -> StructTag {
+        StructTag {
+            address,
+            module: Identifier::new(module).unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params: vec![],
+        }
+    }
+
+    fn change(handle: u128, key_type: TypeTag, value_type: TypeTag) -> TableChange {
+        TableChange {
+            handle: TableHandle(handle),
+            key_type,
+            value_type,
+            op: TableChangeOp::Write,
+        }
+    }
+
+    /// A recording observer that collects every change set it is handed, so a
+    /// test can assert both which observers fired and the exact subset each saw.
+    fn recorder() -> (TableObserverCallback, Arc<RwLock<Vec<TableChangeSet>>>) {
+        let seen = Arc::new(RwLock::new(Vec::new()));
+        let sink = seen.clone();
+        let callback: TableObserverCallback =
+            Arc::new(move |cs: &TableChangeSet| sink.write().unwrap().push(cs.clone()));
+        (callback, seen)
+    }
+
+    #[test]
+    fn handle_filter_delivers_only_the_matching_handle() {
+        let registry = TableObserverRegistry::default();
+        let (callback, seen) = recorder();
+        registry.register(TableObserverFilter::Handle(TableHandle(7)), callback);
+
+        registry.notify(&TableChangeSet {
+            changes: vec![
+                change(7, TypeTag::U64, TypeTag::U64),
+                change(9, TypeTag::U64, TypeTag::U64),
+            ],
+        });
+
+        let seen = seen.read().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].changes.len(), 1);
+        assert_eq!(seen[0].changes[0].handle, TableHandle(7));
+    }
+
+    #[test]
+    fn struct_tag_filter_matches_key_or_value_type() {
+        let registry = TableObserverRegistry::default();
+        let (callback, seen) = recorder();
+        let tag = struct_tag(AccountAddress::ONE, "coin", "Coin");
+        registry.register(TableObserverFilter::StructTag(tag.clone()), callback);
+
+        registry.notify(&TableChangeSet {
+            changes: vec![
+                // value_type mentions the struct -> matches.
+                change(1, TypeTag::U64, TypeTag::Struct(tag)),
+                // neither side mentions it -> filtered out.
+                change(2, TypeTag::U64, TypeTag::Bool),
+            ],
+        });
+
+        let seen = seen.read().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].changes.len(), 1);
+        assert_eq!(seen[0].changes[0].handle, TableHandle(1));
+    }
+
+    #[test]
+    fn account_filter_matches_owning_address() {
+        let registry = TableObserverRegistry::default();
+        let (callback, seen) = recorder();
+        let owner = AccountAddress::from_hex_literal("0x42").unwrap();
+        registry.register(TableObserverFilter::Account(owner), callback);
+
+        registry.notify(&TableChangeSet {
+            changes: vec![
+                change(1, TypeTag::Struct(struct_tag(owner, "thing", "T")), TypeTag::U64),
+                change(2, TypeTag::U64, TypeTag::U64),
+            ],
+        });
+
+        let seen = seen.read().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].changes[0].handle, TableHandle(1));
+    }
+
+    #[test]
+    fn empty_sets_do_not_fire_and_deregister_stops_delivery() {
+        let registry = TableObserverRegistry::default();
+        let (callback, seen) = recorder();
+        let id = registry.register(TableObserverFilter::Any, callback);
+
+        // An empty change set must never invoke a callback.
+        registry.notify(&TableChangeSet::default());
+        assert!(seen.read().unwrap().is_empty());
+
+        registry.notify(&TableChangeSet {
+            changes: vec![change(1, TypeTag::U64, TypeTag::U64)],
+        });
+        assert_eq!(seen.read().unwrap().len(), 1);
+
+        assert!(registry.deregister(id));
+        registry.notify(&TableChangeSet {
+            changes: vec![change(2, TypeTag::U64, TypeTag::U64)],
+        });
+        assert_eq!(seen.read().unwrap().len(), 1);
+    }
+}
+
+#[cfg(test)]
+mod writer_tests {
+    use super::*;
+    use move_deps::move_binary_format::file_format::AbilitySet;
+    use move_deps::move_core_types::identifier::Identifier;
+    use move_deps::move_resource_viewer::AnnotatedMoveStruct;
+
+    fn table_value(
+        handle: u128,
+        key_type: TypeTag,
+        value_type: TypeTag,
+    ) -> AnnotatedMoveValue {
+        AnnotatedMoveValue::Struct(AnnotatedMoveStruct {
+            abilities: AbilitySet::EMPTY,
+            type_: StructTag {
+                address: AccountAddress::ONE,
+                module: Identifier::new("table").unwrap(),
+                name: Identifier::new("Table").unwrap(),
+                type_params: vec![key_type, value_type],
+            },
+            value: vec![(
+                Identifier::new("handle").unwrap(),
+                AnnotatedMoveValue::U128(handle),
+            )],
+        })
+    }
+
+    #[test]
+    fn parse_table_info_recursion_flushes_through_the_mock() {
+        // A struct that is not itself a Table but holds one in a field: the
+        // recursion must descend into it and surface the inner handle.
+        let outer = AnnotatedMoveValue::Struct(AnnotatedMoveStruct {
+            abilities: AbilitySet::EMPTY,
+            type_: StructTag {
+                address: AccountAddress::ONE,
+                module: Identifier::new("wallet").unwrap(),
+                name: Identifier::new("Wallet").unwrap(),
+                type_params: vec![],
+            },
+            value: vec![(
+                Identifier::new("coins").unwrap(),
+                table_value(42, TypeTag::U64, TypeTag::Address),
+            )],
+        });
+
+        let mut puts = IndexBatch::default();
+        let mut discovered = HashMap::new();
+        AptosDB::parse_table_info(&outer, &mut puts, &mut discovered).unwrap();
+
+        // The recursion both seeds the in-batch cache and queues the put.
+        assert!(discovered.contains_key(&TableHandle(42)));
+        assert_eq!(puts.table_info.len(), 1);
+
+        // Flushing through the mock captures the mapping without any RocksDB.
+        let mut writer = InMemoryIndexWriter::default();
+        puts.write_through(&mut writer).unwrap();
+        writer.commit_batch(7).unwrap();
+
+        assert_eq!(writer.head, Some(7));
+        let info = writer.table_info.get(&TableHandle(42)).unwrap();
+        assert_eq!(info.key_type, TypeTag::U64);
+        assert_eq!(info.value_type, TypeTag::Address);
+    }
+
+    #[test]
+    fn mock_applies_item_puts_and_info_deletes() {
+        let mut writer = InMemoryIndexWriter::default();
+
+        let key = TableItemIndexKey {
+            handle: TableHandle(1),
+            encoded_key: bcs::to_bytes(&"k".to_string()).unwrap(),
+        };
+        writer
+            .put_table_item(
+                key.clone(),
+                TableItemIndexValue {
+                    version: 3,
+                    rendered: "v".to_string(),
+                },
+            )
+            .unwrap();
+        writer.put_table_info(TableHandle(1), &TableInfo {
+            key_type: TypeTag::U64,
+            value_type: TypeTag::U64,
+        })
+        .unwrap();
+        writer.delete_table_info(TableHandle(1)).unwrap();
+        writer.commit_batch(3).unwrap();
+
+        assert!(writer.table_info.is_empty());
+        assert_eq!(writer.table_items.get(&key).unwrap().rendered, "v");
+        assert_eq!(writer.head, Some(3));
+    }
+}
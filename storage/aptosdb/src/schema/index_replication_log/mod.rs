@@ -0,0 +1,70 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines the physical storage schema for the index replication
+//! log: the durable, ordered record of every mutation shipped to the index.
+//!
+//! The primary index keeps only the latest version per key, so it cannot be
+//! replayed from a given version. This log closes that gap: each committed batch
+//! appends its mutations here, tagged with the head version the batch advanced
+//! the index to and a per-batch sequence number that preserves the original
+//! order. A lagging or restarted external replica reports its last-seen version
+//! and the node replays everything past it, making catch-up deterministic.
+//!
+//! ```text
+//! |<------- key -------->|<--- value --->|
+//! | version | seq        | IndexMutation |
+//! ```
+
+use crate::indexer::IndexMutation;
+use crate::schema::{ensure_slice_len_gt, INDEX_REPLICATION_LOG_CF_NAME};
+use anyhow::Result;
+use aptos_types::transaction::Version;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(
+    IndexReplicationLogSchema,
+    ReplicationLogKey,
+    IndexMutation,
+    INDEX_REPLICATION_LOG_CF_NAME
+);
+
+/// Composite key: the batch's head version followed by a per-batch sequence
+/// number, both big-endian so a forward scan yields mutations in commit order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct ReplicationLogKey {
+    pub version: Version,
+    pub seq: u32,
+}
+
+impl KeyCodec<IndexReplicationLogSchema> for ReplicationLogKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut encoded = Vec::with_capacity(size_of::<Version>() + size_of::<u32>());
+        encoded.write_u64::<BigEndian>(self.version)?;
+        encoded.write_u32::<BigEndian>(self.seq)?;
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_gt(data, size_of::<Version>() + size_of::<u32>() - 1)?;
+        let mut reader = data;
+        let version = reader.read_u64::<BigEndian>()?;
+        let seq = reader.read_u32::<BigEndian>()?;
+        Ok(Self { version, seq })
+    }
+}
+
+impl ValueCodec<IndexReplicationLogSchema> for IndexMutation {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
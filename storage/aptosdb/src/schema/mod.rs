@@ -0,0 +1,57 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines the physical storage schema used by the internal
+//! indexer's `index_db`, including the set of column families that back it and
+//! small helpers shared by the per-schema codecs.
+
+use anyhow::{ensure, Result};
+use schemadb::ColumnFamilyName;
+
+pub mod index_replication_log;
+pub mod table_index_metadata;
+pub mod table_item_index;
+
+pub const DEFAULT_COLUMN_FAMILY_NAME: ColumnFamilyName = "default";
+/// `TableHandle -> TableInfo`, the primary table-info index.
+pub const TABLE_INFO_CF_NAME: ColumnFamilyName = "table_info";
+/// `(TableHandle, encoded_key) -> (latest Version, rendered value)`, the reverse
+/// item index.
+pub const TABLE_ITEM_INDEX_CF_NAME: ColumnFamilyName = "table_item_index";
+/// Single-row metadata such as the durable index head version.
+pub const TABLE_INDEX_METADATA_CF_NAME: ColumnFamilyName = "table_index_metadata";
+/// Ordered, durable log of shipped index mutations for replica catch-up.
+pub const INDEX_REPLICATION_LOG_CF_NAME: ColumnFamilyName = "index_replication_log";
+
+/// The column families `index_db` is opened with. The handle-before-item
+/// dependency lives in the write path, not here; ordering of this list is
+/// irrelevant beyond `default` being present for RocksDB.
+pub fn column_families() -> Vec<ColumnFamilyName> {
+    vec![
+        DEFAULT_COLUMN_FAMILY_NAME,
+        TABLE_INFO_CF_NAME,
+        TABLE_ITEM_INDEX_CF_NAME,
+        TABLE_INDEX_METADATA_CF_NAME,
+        INDEX_REPLICATION_LOG_CF_NAME,
+    ]
+}
+
+pub(crate) fn ensure_slice_len_eq(data: &[u8], len: usize) -> Result<()> {
+    ensure!(
+        data.len() == len,
+        "Unexpected data len {}, expected {}.",
+        data.len(),
+        len,
+    );
+    Ok(())
+}
+
+pub(crate) fn ensure_slice_len_gt(data: &[u8], len: usize) -> Result<()> {
+    ensure!(
+        data.len() > len,
+        "Unexpected data len {}, expected greater than {}.",
+        data.len(),
+        len,
+    );
+    Ok(())
+}
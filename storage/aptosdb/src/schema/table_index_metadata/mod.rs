@@ -0,0 +1,64 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines the physical storage schema for table-index metadata.
+//!
+//! Currently it holds a single row: the monotonically increasing "head"
+//! version, i.e. the highest transaction version whose table changes have been
+//! durably indexed. A lagging or restarted external replica reads this to learn
+//! how far the local index has advanced and request everything since its own
+//! last-seen version.
+//!
+//! ```text
+//! |<--- key --->|<-- value -->|
+//! | metadata_key| version     |
+//! ```
+
+use crate::schema::{ensure_slice_len_eq, TABLE_INDEX_METADATA_CF_NAME};
+use anyhow::Result;
+use aptos_types::transaction::Version;
+use byteorder::{BigEndian, ReadBytesExt};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(
+    TableIndexMetadataSchema,
+    MetadataKey,
+    Version,
+    TABLE_INDEX_METADATA_CF_NAME
+);
+
+/// Enumerates the metadata rows. A single-byte discriminant keeps the key space
+/// open for future additions without reformatting existing rows.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum MetadataKey {
+    /// The highest version durably reflected in the index.
+    IndexHead = 0,
+}
+
+impl KeyCodec<TableIndexMetadataSchema> for MetadataKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(vec![(*self).into()])
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 1)?;
+        Ok(MetadataKey::try_from(data[0])?)
+    }
+}
+
+impl ValueCodec<TableIndexMetadataSchema> for Version {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Version>())?;
+        Ok((&data[..]).read_u64::<BigEndian>()?)
+    }
+}
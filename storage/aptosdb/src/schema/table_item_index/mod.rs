@@ -0,0 +1,82 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines the physical storage schema for the table-item index,
+//! which maps a decoded table item key to the latest version at which it was
+//! written, together with that version's value rendered to a stable string.
+//!
+//! ```text
+//! |<----------- key ------------>|<------ value ------>|
+//! | table_handle | encoded_key   | version | rendered |
+//! ```
+//!
+//! `encoded_key` is the BCS serialization of the item key's stable string
+//! rendering (see `indexer`), which keeps lexical ordering grouped by handle so
+//! that a prefix scan yields all live keys of a single table.
+
+use crate::schema::{ensure_slice_len_gt, TABLE_ITEM_INDEX_CF_NAME};
+use anyhow::Result;
+use aptos_types::state_store::table::TableHandle;
+use aptos_types::transaction::Version;
+use byteorder::{BigEndian, ReadBytesExt};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::mem::size_of;
+
+define_schema!(
+    TableItemIndexSchema,
+    TableItemIndexKey,
+    TableItemIndexValue,
+    TABLE_ITEM_INDEX_CF_NAME
+);
+
+/// Composite key: the owning table handle followed by the encoded item key.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct TableItemIndexKey {
+    pub handle: TableHandle,
+    pub encoded_key: Vec<u8>,
+}
+
+impl KeyCodec<TableItemIndexSchema> for TableItemIndexKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut encoded = Vec::with_capacity(size_of::<u128>() + self.encoded_key.len());
+        // Big-endian so that RocksDB's lexical ordering groups a handle's items
+        // contiguously, enabling cheap prefix scans per table.
+        encoded.write_all(&self.handle.0.to_be_bytes())?;
+        encoded.write_all(&self.encoded_key)?;
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_gt(data, size_of::<u128>() - 1)?;
+        let mut reader = data;
+        let handle = TableHandle(reader.read_u128::<BigEndian>()?);
+        Ok(Self {
+            handle,
+            encoded_key: reader.to_vec(),
+        })
+    }
+}
+
+/// The indexed value for an item: the latest version that wrote it and that
+/// value rendered to the same stable string form used for keys. `rendered` is
+/// empty when the value could not be materialized (e.g. an aggregator delta).
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TableItemIndexValue {
+    pub version: Version,
+    pub rendered: String,
+}
+
+impl ValueCodec<TableItemIndexSchema> for TableItemIndexValue {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}